@@ -1,17 +1,120 @@
-use clap::{crate_authors, crate_version, Clap};
+use clap::{crate_authors, crate_version, ArgEnum, Clap};
 use futures::{future, stream::TryStreamExt};
 use itertools::Itertools;
 use octocrab::Octocrab;
-use octocrabby::{block_user, check_follow, cli, models::UserInfo, parse_repo_path, pull_requests};
+use octocrabby::models::{ContributorRecord, ReviewRequestRecord, UserInfo, UserRecord};
+use octocrabby::retry::RetryConfig;
+use octocrabby::{
+    block_user, check_follow, cli, get_blocks, get_followers, get_following, parse_repo_path,
+    pull_requests, review_requests, unblock_user,
+};
+use serde::Serialize;
 use std::collections::{HashMap, HashSet};
 
 type Void = Result<(), Box<dyn std::error::Error>>;
 
+/// The shape to render a list command's records in.
+#[derive(ArgEnum, Clone, Copy)]
+enum Format {
+    Csv,
+    Json,
+    Table,
+}
+
+/// Write a list command's records to stdout in the requested format.
+///
+/// `csv` emits a header row followed by the records, `json` a pretty-printed
+/// array, and `table` a column-aligned ASCII table. The `csv` and `table`
+/// headers come from the first record, so an empty list prints no header.
+fn emit<T: Serialize>(records: &[T], format: Format) -> Void {
+    match format {
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(std::io::stdout());
+            for record in records {
+                writer.serialize(record)?;
+            }
+            writer.flush()?;
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(std::io::stdout(), records)?;
+            println!();
+        }
+        Format::Table => render_table(records)?,
+    }
+
+    Ok(())
+}
+
+/// Render records as an aligned ASCII table, taking the column names from the
+/// first record's serialized fields.
+fn render_table<T: Serialize>(records: &[T]) -> Void {
+    let rows = records
+        .iter()
+        .map(serde_json::to_value)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let headers: Vec<String> = match rows.first().and_then(|row| row.as_object()) {
+        Some(object) => object.keys().cloned().collect(),
+        None => return Ok(()),
+    };
+
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| headers.iter().map(|header| cell_to_string(&row[header])).collect())
+        .collect();
+
+    let widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(column, header)| {
+            cells
+                .iter()
+                .map(|row| row[column].len())
+                .chain(std::iter::once(header.len()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let render_row = |row: &[String]| {
+        row.iter()
+            .zip(&widths)
+            .map(|(value, width)| format!("{:<width$}", value, width = width))
+            .join("  ")
+    };
+
+    println!("{}", render_row(&headers));
+    for row in &cells {
+        println!("{}", render_row(row));
+    }
+
+    Ok(())
+}
+
+/// Format a JSON value as a plain table cell, rendering strings without quotes
+/// and nulls as an empty cell.
+fn cell_to_string(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(value) => value.clone(),
+        other => other.to_string(),
+    }
+}
+
 #[tokio::main]
 async fn main() -> Void {
     let opts: Opts = Opts::parse();
     let _ = cli::init_logging(opts.verbose);
-    let instance = octocrabby::init(opts.token)?;
+    let retry = if opts.no_retry {
+        RetryConfig::disabled()
+    } else {
+        RetryConfig {
+            max_retries: opts.max_retries,
+            enabled: true,
+        }
+    };
+    let instance = octocrabby::init(opts.token, retry)?;
+    let format = opts.format;
 
     match opts.command {
         Command::BlockUsers => {
@@ -24,6 +127,8 @@ async fn main() -> Void {
             }
 
             for username in usernames {
+                // The field may also be an `id:12345` token, resolved here to the current login
+                let username = octocrabby::resolve_login(&instance, &username).await?;
                 if block_user(&instance, &username).await? {
                     log::info!("Successfully blocked {}", username)
                 } else {
@@ -31,29 +136,34 @@ async fn main() -> Void {
                 };
             }
         }
+        Command::UnblockUsers => {
+            // As with `BlockUsers`, only the first field is used; it may be a login or an
+            // `id:12345` token, which is resolved to the current login
+            let mut reader = csv::Reader::from_reader(std::io::stdin());
+            let mut usernames = vec![];
+
+            for record in reader.records() {
+                usernames.push(record?.get(0).unwrap().to_string());
+            }
+
+            for username in usernames {
+                let username = octocrabby::resolve_login(&instance, &username).await?;
+                if unblock_user(&instance, &username).await? {
+                    log::info!("Successfully unblocked {}", username)
+                };
+            }
+        }
         Command::ListFollowers => {
-            octocrabby::get_followers(&instance)
-                .try_for_each(|user| {
-                    println!("{},{}", user.login, user.id);
-                    future::ok(())
-                })
-                .await?
+            let records = user_records(get_followers(&instance)).await?;
+            emit(&records, format)?;
         }
         Command::ListFollowing => {
-            octocrabby::get_following(&instance)
-                .try_for_each(|user| {
-                    println!("{},{}", user.login, user.id);
-                    future::ok(())
-                })
-                .await?
+            let records = user_records(get_following(&instance)).await?;
+            emit(&records, format)?;
         }
         Command::ListBlocks => {
-            octocrabby::get_blocks(&instance)
-                .try_for_each(|user| {
-                    println!("{},{}", user.login, user.id);
-                    future::ok(())
-                })
-                .await?
+            let records = user_records(get_blocks(&instance)).await?;
+            emit(&records, format)?;
         }
         Command::ListPrContributors { repo_path } => {
             if let Some((owner, repo)) = parse_repo_path(&repo_path) {
@@ -93,11 +203,19 @@ async fn main() -> Void {
                         None
                     };
 
-                let mut writer = csv::Writer::from_writer(std::io::stdout());
+                let mut records = Vec::with_capacity(results.len());
 
                 for (username, user_id, pr_count, first_pr_date) in results {
-                    let mut record =
-                        vec![username.clone(), user_id.to_string(), pr_count.to_string()];
+                    let mut record = ContributorRecord {
+                        login: username.clone(),
+                        id: user_id.0,
+                        pull_requests: pr_count,
+                        age: None,
+                        name: None,
+                        you_follow: None,
+                        follows_you: None,
+                        twitter_username: None,
+                    };
 
                     // Add other fields to the record if you're authenticated
                     if let Some(AdditionalUserInfo {
@@ -118,15 +236,39 @@ async fn main() -> Void {
                             }
                         };
 
-                        record.push(age.to_string());
-                        record.push(name);
-                        record.push(you_follow.contains(&username).to_string());
-                        record.push(follows_you.contains(&username).to_string());
-                        record.push(twitter_username);
+                        record.age = Some(age);
+                        record.name = Some(name);
+                        record.you_follow = Some(you_follow.contains(&username));
+                        record.follows_you = Some(follows_you.contains(&username));
+                        record.twitter_username = Some(twitter_username);
                     };
 
-                    writer.write_record(&record)?;
+                    records.push(record);
                 }
+
+                emit(&records, format)?;
+            } else {
+                log::error!("Invalid repository path: {}", repo_path);
+            }
+        }
+        Command::ListReviewRequests { repo_path } => {
+            if let Some((owner, repo)) = parse_repo_path(&repo_path) {
+                log::info!("Loading pull requests awaiting your review");
+                let prs = review_requests(&instance, owner, repo).await?;
+
+                let now = chrono::Utc::now();
+                let records = prs
+                    .into_iter()
+                    .map(|pr| ReviewRequestRecord {
+                        number: pr.number,
+                        title: pr.title.unwrap_or_default(),
+                        author: pr.user.login,
+                        created_at: pr.created_at.to_rfc3339(),
+                        age: (now - pr.created_at).num_days(),
+                    })
+                    .collect::<Vec<_>>();
+
+                emit(&records, format)?;
             } else {
                 log::error!("Invalid repository path: {}", repo_path);
             }
@@ -155,6 +297,15 @@ struct Opts {
     #[clap(short, long, parse(from_occurrences))]
     /// Logging verbosity
     verbose: i32,
+    /// Output format for list commands
+    #[clap(long, arg_enum, default_value = "csv", global = true)]
+    format: Format,
+    /// Maximum number of times to retry a rate-limited or transient request
+    #[clap(long, default_value = "5", global = true)]
+    max_retries: u32,
+    /// Disable retrying of rate-limited and transient failures
+    #[clap(long, global = true)]
+    no_retry: bool,
     #[clap(subcommand)]
     command: Command,
 }
@@ -163,6 +314,8 @@ struct Opts {
 enum Command {
     /// Block a list of users provided in CSV format to stdin
     BlockUsers,
+    /// Unblock a list of users provided in CSV format to stdin
+    UnblockUsers,
     /// List the authenticated user's followers in CSV format to stdout
     ListFollowers,
     /// List accounts the authenticated user follows in CSV format to stdout
@@ -175,6 +328,12 @@ enum Command {
         #[clap(short, long)]
         repo_path: String,
     },
+    /// List open pull requests awaiting your review to stdout, in the chosen `--format`
+    ListReviewRequests {
+        /// The repository to check for pull requests
+        #[clap(short, long)]
+        repo_path: String,
+    },
     /// Check whether one user follows another
     CheckFollow {
         /// The possibly followed user
@@ -186,6 +345,20 @@ enum Command {
     },
 }
 
+/// Collect a stream of users into serializable [`UserRecord`] rows.
+async fn user_records<S>(stream: S) -> octocrab::Result<Vec<UserRecord>>
+where
+    S: futures::Stream<Item = octocrab::Result<octocrab::models::User>>,
+{
+    stream
+        .map_ok(|user| UserRecord {
+            login: user.login,
+            id: user.id.0,
+        })
+        .try_collect()
+        .await
+}
+
 struct AdditionalUserInfo {
     follows_you: HashSet<String>,
     you_follow: HashSet<String>,
@@ -197,13 +370,13 @@ async fn load_additional_user_info(
     usernames: &[String],
 ) -> octocrab::Result<AdditionalUserInfo> {
     log::info!("Loading follower information");
-    let follows_you = octocrabby::get_followers(&instance)
+    let follows_you = get_followers(instance)
         .and_then(|user| future::ok(user.login))
         .try_collect()
         .await?;
 
     log::info!("Loading following information");
-    let you_follow = octocrabby::get_following(&instance)
+    let you_follow = get_following(instance)
         .and_then(|user| future::ok(user.login))
         .try_collect()
         .await?;
@@ -212,11 +385,8 @@ async fn load_additional_user_info(
         "Loading additional user information for {} users",
         usernames.len()
     );
-    let user_info: HashMap<String, UserInfo> = octocrabby::get_users_info(&instance, &usernames)
-        .await?
-        .into_iter()
-        .map(|info| (info.login.clone(), info))
-        .collect();
+    let user_info: HashMap<String, UserInfo> =
+        octocrabby::get_users_info(instance, usernames).await?;
 
     Ok(AdditionalUserInfo {
         follows_you,
@@ -0,0 +1,26 @@
+//! Shared command-line plumbing.
+
+use log::LevelFilter;
+
+/// Initialise logging, mapping the number of `-v` flags to a level filter.
+///
+/// The default (no `-v`) is silent; each additional occurrence turns up the
+/// verbosity one step, all the way to `trace`.
+pub fn init_logging(verbosity: i32) -> Result<(), log::SetLoggerError> {
+    let level = match verbosity {
+        0 => LevelFilter::Off,
+        1 => LevelFilter::Error,
+        2 => LevelFilter::Warn,
+        3 => LevelFilter::Info,
+        4 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    };
+
+    fern::Dispatch::new()
+        .format(|out, message, record| {
+            out.finish(format_args!("[{}] {}", record.level(), message))
+        })
+        .level(level)
+        .chain(std::io::stderr())
+        .apply()
+}
@@ -0,0 +1,467 @@
+use futures::{Stream, TryStreamExt};
+use octocrab::{Octocrab, Page};
+use reqwest::StatusCode;
+
+pub mod cli;
+pub mod models;
+pub mod retry;
+
+use crate::models::UserInfo;
+use crate::retry::{RetryConfig, RetryMiddleware};
+use octocrab::models::{pulls::PullRequest, IssueState, User};
+
+/// Build an [`Octocrab`] instance, authenticating with the given personal
+/// access token when one is provided.
+///
+/// A token is optional because several of the read-only endpoints (listing a
+/// repository's pull requests, for example) work unauthenticated, just with a
+/// stricter rate limit.
+///
+/// When `retry` is enabled the underlying client is wrapped in a
+/// [`RetryMiddleware`], so long-running commands ride out GitHub's secondary
+/// rate limits instead of erroring out mid-stream.
+pub fn init(token: Option<String>, retry: RetryConfig) -> octocrab::Result<Octocrab> {
+    let mut builder = Octocrab::builder();
+
+    if let Some(token) = token {
+        builder = builder.personal_token(token);
+    }
+
+    if retry.enabled {
+        let client = reqwest_middleware::ClientBuilder::new(reqwest::Client::new())
+            .with(RetryMiddleware::new(retry))
+            .build();
+        builder = builder.with_client(client);
+    }
+
+    builder.build()
+}
+
+/// Stream every page of a paginated endpoint, yielding the items one at a time.
+fn paginate<'a, T: serde::de::DeserializeOwned + 'a>(
+    instance: &'a Octocrab,
+    route: String,
+) -> impl Stream<Item = octocrab::Result<T>> + 'a {
+    async_stream::try_stream! {
+        let mut next = Some(instance.get::<Page<T>, _, ()>(&route, None).await?);
+
+        while let Some(page) = next {
+            for item in page.items {
+                yield item;
+            }
+
+            next = instance.get_page(&page.next).await?;
+        }
+    }
+}
+
+/// Stream the authenticated user's followers.
+pub fn get_followers(instance: &Octocrab) -> impl Stream<Item = octocrab::Result<User>> + '_ {
+    paginate(instance, "user/followers".to_string())
+}
+
+/// Stream the accounts the authenticated user follows.
+pub fn get_following(instance: &Octocrab) -> impl Stream<Item = octocrab::Result<User>> + '_ {
+    paginate(instance, "user/following".to_string())
+}
+
+/// Stream the accounts the authenticated user blocks.
+pub fn get_blocks(instance: &Octocrab) -> impl Stream<Item = octocrab::Result<User>> + '_ {
+    paginate(instance, "user/blocks".to_string())
+}
+
+/// Stream every pull request (in any state) for the given repository.
+pub fn pull_requests<'a>(
+    instance: &'a Octocrab,
+    owner: &'a str,
+    repo: &'a str,
+) -> impl Stream<Item = octocrab::Result<PullRequest>> + 'a {
+    paginate(
+        instance,
+        format!("repos/{}/{}/pulls?state=all&per_page=100", owner, repo),
+    )
+}
+
+/// Resolve a `login` or an `id:12345` token to the account's current login.
+///
+/// GitHub logins are mutable, so a blocklist or `--user` argument captured
+/// months ago can silently retarget a renamed or re-registered account. An
+/// `id:` token names the stable numeric user ID instead, which we resolve to
+/// the current login via the `user/{id}` endpoint before calling the follower
+/// and blocking APIs; a bare token is already a login and is returned as-is.
+pub async fn resolve_login(instance: &Octocrab, token: &str) -> octocrab::Result<String> {
+    match token.strip_prefix("id:") {
+        Some(id) => {
+            let user = instance
+                .get::<User, _, ()>(&format!("user/{}", id.trim()), None)
+                .await?;
+
+            Ok(user.login)
+        }
+        None => Ok(token.to_string()),
+    }
+}
+
+/// Check whether `follower` follows `user`.
+///
+/// Either side may be given as a `login` or an `id:12345` token; both are
+/// resolved to a current login via [`resolve_login`] first.
+///
+/// GitHub answers this with a `204` when the follow relationship exists and a
+/// `404` when it doesn't, so we only have to look at the status code.
+pub async fn check_follow(
+    instance: &Octocrab,
+    follower: &str,
+    user: &str,
+) -> octocrab::Result<bool> {
+    let follower = resolve_login(instance, follower).await?;
+    let user = resolve_login(instance, user).await?;
+    let route = format!("users/{}/following/{}", follower, user);
+    let response = instance._get(instance.absolute_url(route)?, None::<&()>).await?;
+
+    Ok(response.status() == StatusCode::NO_CONTENT)
+}
+
+/// Check whether the authenticated user currently blocks `username`.
+pub async fn is_blocked(instance: &Octocrab, username: &str) -> octocrab::Result<bool> {
+    let route = format!("user/blocks/{}", username);
+    let response = instance._get(instance.absolute_url(route)?, None::<&()>).await?;
+
+    Ok(response.status() == StatusCode::NO_CONTENT)
+}
+
+/// Block `username`, returning `true` if the user wasn't already blocked.
+pub async fn block_user(instance: &Octocrab, username: &str) -> octocrab::Result<bool> {
+    if is_blocked(instance, username).await? {
+        Ok(false)
+    } else {
+        let route = format!("user/blocks/{}", username);
+        instance._put(instance.absolute_url(route)?, None::<&()>).await?;
+
+        Ok(true)
+    }
+}
+
+/// Unblock `username`, returning `true` if the user was actually blocked.
+///
+/// Like [`block_user`] this checks the is-blocked endpoint first so the call is
+/// idempotent: unblocking someone who isn't blocked logs a warning and reports
+/// `false` rather than erroring.
+pub async fn unblock_user(instance: &Octocrab, username: &str) -> octocrab::Result<bool> {
+    if is_blocked(instance, username).await? {
+        let route = format!("user/blocks/{}", username);
+        instance._delete(instance.absolute_url(route)?, None::<&()>).await?;
+
+        Ok(true)
+    } else {
+        log::warn!("{} was not blocked", username);
+
+        Ok(false)
+    }
+}
+
+/// The top-level shape of a GraphQL response, keeping both `data` and any
+/// `errors` so partial successes can be salvaged.
+#[derive(serde::Deserialize)]
+struct GraphQlResponse {
+    data: Option<std::collections::HashMap<String, Option<UserInfo>>>,
+    errors: Option<Vec<serde_json::Value>>,
+}
+
+/// The maximum number of aliased user nodes to request in a single query, kept
+/// well under GitHub's node and cost limits.
+const USER_INFO_CHUNK_SIZE: usize = 100;
+
+/// Look up extended profile information for each of the given logins.
+///
+/// This batches the lookups through the GraphQL API, aliasing up to
+/// [`USER_INFO_CHUNK_SIZE`] users per request, which turns a contributor list
+/// of several hundred accounts from that many REST round-trips into a handful
+/// of queries. Logins GraphQL can't resolve (renamed or deleted accounts that
+/// come back as a top-level `errors` entry) are skipped, and the successful
+/// aliases in the same response are still kept.
+pub async fn get_users_info(
+    instance: &Octocrab,
+    logins: &[String],
+) -> octocrab::Result<std::collections::HashMap<String, UserInfo>> {
+    let mut result = std::collections::HashMap::with_capacity(logins.len());
+    let mut skipped = 0;
+
+    for chunk in logins.chunks(USER_INFO_CHUNK_SIZE) {
+        let mut signature = String::from("query (");
+        let mut body = String::new();
+        let mut variables = serde_json::Map::with_capacity(chunk.len());
+
+        for (index, login) in chunk.iter().enumerate() {
+            if index > 0 {
+                signature.push_str(", ");
+            }
+            signature.push_str(&format!("$u{}: String!", index));
+            body.push_str(&format!(
+                "  u{0}: user(login: $u{0}) {{ login databaseId name twitterUsername createdAt followers {{ totalCount }} }}\n",
+                index
+            ));
+            variables.insert(format!("u{}", index), serde_json::Value::String(login.clone()));
+        }
+
+        signature.push_str(") {\n");
+        let query = format!("{}{}}}\n", signature, body);
+
+        let response: GraphQlResponse = instance
+            .graphql(&serde_json::json!({ "query": query, "variables": variables }))
+            .await?;
+
+        if response.errors.is_some() {
+            log::debug!("GraphQL reported errors while resolving a user chunk");
+        }
+
+        let mut resolved = 0;
+
+        if let Some(data) = response.data {
+            for info in data.into_values().flatten() {
+                resolved += 1;
+                result.insert(info.login.clone(), info);
+            }
+        }
+
+        skipped += chunk.len() - resolved;
+    }
+
+    if skipped > 0 {
+        log::warn!("Skipped {} users that GraphQL could not resolve", skipped);
+    }
+
+    Ok(result)
+}
+
+/// A team the authenticated user belongs to, as returned by `/user/teams`.
+///
+/// Team slugs are only unique within an organization, so the owning org is
+/// kept alongside the slug and the two are matched together.
+#[derive(serde::Deserialize)]
+struct TeamRef {
+    slug: String,
+    organization: OrgRef,
+}
+
+/// The organization that owns a [`TeamRef`], reduced to its login.
+#[derive(serde::Deserialize)]
+struct OrgRef {
+    login: String,
+}
+
+/// A single review on a pull request, reduced to the reviewer's login.
+#[derive(serde::Deserialize)]
+struct ReviewRef {
+    user: Option<User>,
+}
+
+/// Whether `pr` (owned by `owner`) has a pending review request aimed at
+/// `login` or at one of the `teams` the user belongs to.
+///
+/// Team membership is matched on the `(organization, slug)` pair rather than
+/// the bare slug: slugs recur across orgs, so a `reviewers` team in one org
+/// must not satisfy a request for a same-named team in another. The PR's
+/// `owner` is the organization that can request its own teams.
+fn requested_from(
+    pr: &PullRequest,
+    login: &str,
+    owner: &str,
+    teams: &std::collections::HashSet<(String, String)>,
+) -> bool {
+    let from_user = pr
+        .requested_reviewers
+        .as_ref()
+        .map(|reviewers| reviewers.iter().any(|user| user.login == login))
+        .unwrap_or(false);
+
+    let from_team = pr
+        .requested_teams
+        .as_ref()
+        .map(|requested| {
+            requested
+                .iter()
+                .any(|team| teams.contains(&(owner.to_string(), team.slug.clone())))
+        })
+        .unwrap_or(false);
+
+    from_user || from_team
+}
+
+/// Whether `login` has already submitted a review on the given pull request.
+async fn has_reviewed(
+    instance: &Octocrab,
+    owner: &str,
+    repo: &str,
+    number: u64,
+    login: &str,
+) -> octocrab::Result<bool> {
+    let route = format!("repos/{}/{}/pulls/{}/reviews?per_page=100", owner, repo, number);
+    let reviews = paginate::<ReviewRef>(instance, route);
+    futures::pin_mut!(reviews);
+
+    while let Some(review) = reviews.try_next().await? {
+        if review.user.as_ref().map(|user| user.login == login).unwrap_or(false) {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// List open pull requests in a repository that are awaiting a review from the
+/// authenticated user, either directly or via one of their teams.
+///
+/// Pull requests the user has already reviewed are excluded, so the result is
+/// exactly the set still sitting in their review queue.
+pub async fn review_requests(
+    instance: &Octocrab,
+    owner: &str,
+    repo: &str,
+) -> octocrab::Result<Vec<PullRequest>> {
+    let current = instance.current().user().await?.login;
+
+    let teams: std::collections::HashSet<(String, String)> = match instance
+        .get::<Vec<TeamRef>, _, ()>("user/teams", None)
+        .await
+    {
+        Ok(teams) => teams
+            .into_iter()
+            .map(|team| (team.organization.login, team.slug))
+            .collect(),
+        Err(error) => {
+            log::warn!(
+                "Could not load team memberships ({}); team-requested reviews will be omitted",
+                error
+            );
+            std::collections::HashSet::new()
+        }
+    };
+
+    let open = pull_requests(instance, owner, repo)
+        .try_filter(|pr| futures::future::ready(matches!(pr.state, Some(IssueState::Open))))
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let mut awaiting = Vec::new();
+
+    for pr in open {
+        if requested_from(&pr, &current, owner, &teams)
+            && !has_reviewed(instance, owner, repo, pr.number, &current).await?
+        {
+            awaiting.push(pr);
+        }
+    }
+
+    Ok(awaiting)
+}
+
+/// Split an `owner/repo` path into its two components.
+///
+/// Returns `None` if either side is missing or the path contains more than one
+/// separator.
+pub fn parse_repo_path(path: &str) -> Option<(&str, &str)> {
+    let mut parts = path.splitn(2, '/');
+
+    match (parts.next(), parts.next()) {
+        (Some(owner), Some(repo))
+            if !owner.is_empty() && !repo.is_empty() && !repo.contains('/') =>
+        {
+            Some((owner, repo))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_repo_path_splits_owner_and_repo() {
+        assert_eq!(parse_repo_path("iamricard/octocrabby"), Some(("iamricard", "octocrabby")));
+    }
+
+    #[test]
+    fn parse_repo_path_rejects_malformed_input() {
+        assert_eq!(parse_repo_path("octocrabby"), None);
+        assert_eq!(parse_repo_path("iamricard/"), None);
+        assert_eq!(parse_repo_path("/octocrabby"), None);
+        assert_eq!(parse_repo_path("a/b/c"), None);
+    }
+
+    fn pull_request(reviewers: &[&str], teams: &[&str]) -> PullRequest {
+        let body = serde_json::json!({
+            "url": "https://api.github.com/repos/o/r/pulls/1",
+            "id": 1,
+            "number": 1,
+            "state": "open",
+            "title": "",
+            "requested_reviewers": reviewers
+                .iter()
+                .enumerate()
+                .map(|(id, login)| serde_json::json!({
+                    "login": login,
+                    "id": id + 1,
+                    "node_id": "",
+                    "avatar_url": "https://example.invalid/a.png",
+                    "gravatar_id": "",
+                    "url": "https://api.github.com/users/x",
+                    "html_url": "https://github.com/x",
+                    "followers_url": "https://example.invalid",
+                    "following_url": "https://example.invalid",
+                    "gists_url": "https://example.invalid",
+                    "starred_url": "https://example.invalid",
+                    "subscriptions_url": "https://example.invalid",
+                    "organizations_url": "https://example.invalid",
+                    "repos_url": "https://example.invalid",
+                    "events_url": "https://example.invalid",
+                    "received_events_url": "https://example.invalid",
+                    "type": "User",
+                    "site_admin": false,
+                }))
+                .collect::<Vec<_>>(),
+            "requested_teams": teams
+                .iter()
+                .enumerate()
+                .map(|(id, slug)| serde_json::json!({
+                    "id": id + 1,
+                    "node_id": "",
+                    "url": "https://example.invalid",
+                    "html_url": "https://example.invalid",
+                    "name": slug,
+                    "slug": slug,
+                    "description": null,
+                    "privacy": "closed",
+                    "permission": "pull",
+                    "members_url": "https://example.invalid",
+                    "repositories_url": "https://example.invalid",
+                }))
+                .collect::<Vec<_>>(),
+        });
+
+        serde_json::from_value(body).expect("valid pull request fixture")
+    }
+
+    #[test]
+    fn requested_from_matches_a_direct_reviewer() {
+        let pr = pull_request(&["alice"], &[]);
+        let teams = std::collections::HashSet::new();
+
+        assert!(requested_from(&pr, "alice", "acme", &teams));
+        assert!(!requested_from(&pr, "bob", "acme", &teams));
+    }
+
+    #[test]
+    fn requested_from_scopes_team_slugs_to_the_owner() {
+        let pr = pull_request(&[], &["reviewers"]);
+
+        let mut teams = std::collections::HashSet::new();
+        teams.insert(("acme".to_string(), "reviewers".to_string()));
+
+        // Same slug, same org as the PR owner: a match.
+        assert!(requested_from(&pr, "alice", "acme", &teams));
+        // Same slug but the membership belongs to a different org: no match.
+        assert!(!requested_from(&pr, "alice", "other", &teams));
+    }
+}
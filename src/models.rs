@@ -0,0 +1,68 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Extended profile information for a single GitHub user.
+///
+/// This is the subset of a user node the contributor listing reports on; fields
+/// GitHub may omit (a display name, a linked Twitter handle) are modelled as
+/// [`Option`]. The field names match the GraphQL `User` schema so a batched
+/// query can deserialize straight into this type.
+#[derive(Clone, Debug, Deserialize)]
+pub struct UserInfo {
+    pub login: String,
+    #[serde(rename = "databaseId")]
+    pub database_id: Option<u64>,
+    pub name: Option<String>,
+    #[serde(rename = "twitterUsername")]
+    pub twitter_username: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub followers: FollowerConnection,
+}
+
+/// The `followers { totalCount }` connection carried on a user node.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct FollowerConnection {
+    #[serde(rename = "totalCount")]
+    pub total_count: u64,
+}
+
+/// A row of the PR-contributor listing.
+///
+/// The fields after `pull_requests` are only available to an authenticated
+/// caller, so they're optional and omitted entirely (no column, no JSON key)
+/// when running anonymously.
+#[derive(Clone, Debug, Serialize)]
+pub struct ContributorRecord {
+    pub login: String,
+    pub id: u64,
+    pub pull_requests: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub you_follow: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub follows_you: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub twitter_username: Option<String>,
+}
+
+/// A row of the follower, following, or block listings.
+#[derive(Clone, Debug, Serialize)]
+pub struct UserRecord {
+    pub login: String,
+    pub id: u64,
+}
+
+/// A row of the review-request listing.
+#[derive(Clone, Debug, Serialize)]
+pub struct ReviewRequestRecord {
+    pub number: u64,
+    pub title: String,
+    pub author: String,
+    pub created_at: String,
+    pub age: i64,
+}
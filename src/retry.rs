@@ -0,0 +1,269 @@
+//! Retry, backoff, and rate-limit handling for requests to GitHub.
+//!
+//! Long-running commands on large repositories routinely trip GitHub's
+//! secondary rate limits. Rather than erroring out mid-stream we retry the
+//! offending request, preferring an explicit hint from the response
+//! (`Retry-After` or `X-RateLimit-Reset`) and otherwise falling back to
+//! exponential backoff with jitter.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::HeaderMap;
+use reqwest::{Request, Response, StatusCode};
+use reqwest_middleware::{Middleware, Next, Result};
+use task_local_extensions::Extensions;
+
+/// The base delay for the exponential backoff schedule.
+const BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// The cap on any single computed backoff delay. Explicit `Retry-After` /
+/// `X-RateLimit-Reset` hints are honored in full and are not subject to it.
+const MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// How aggressively to retry failed requests.
+#[derive(Clone, Copy, Debug)]
+pub struct RetryConfig {
+    /// The maximum number of retries before giving up.
+    pub max_retries: u32,
+    /// Whether retrying is enabled at all.
+    pub enabled: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig {
+            max_retries: 5,
+            enabled: true,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// A configuration that never retries.
+    pub fn disabled() -> Self {
+        RetryConfig {
+            max_retries: 0,
+            enabled: false,
+        }
+    }
+}
+
+/// Whether a response with the given status is worth retrying.
+///
+/// GitHub signals both primary and secondary rate limiting with `403` and
+/// `429`; transient server-side failures show up as `5xx`.
+fn is_retryable(status: StatusCode) -> bool {
+    matches!(status, StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS)
+        || status.is_server_error()
+}
+
+/// Extract an explicit wait hint from a response's headers.
+///
+/// A `Retry-After` (in seconds) takes precedence; otherwise an exhausted
+/// `X-RateLimit-Remaining` paired with an `X-RateLimit-Reset` epoch is turned
+/// into the time remaining until the reset.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    if let Some(seconds) = headers
+        .get("retry-after")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+    {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let remaining = headers
+        .get("x-ratelimit-remaining")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())?;
+
+    if remaining > 0 {
+        return None;
+    }
+
+    let reset = headers
+        .get("x-ratelimit-reset")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_secs();
+
+    Some(Duration::from_secs(reset.saturating_sub(now)))
+}
+
+/// Add equal jitter to a delay: keep half of it fixed and randomise the rest,
+/// which spreads retries out without letting them collapse to zero.
+fn with_jitter(delay: Duration) -> Duration {
+    let half = delay / 2;
+    half + half.mul_f64(rand::random::<f64>())
+}
+
+/// The delay before the `attempt`-th retry (zero-indexed), or `None` if the
+/// request should not be retried.
+fn backoff(config: &RetryConfig, attempt: u32, hint: Option<Duration>) -> Option<Duration> {
+    if !config.enabled || attempt >= config.max_retries {
+        return None;
+    }
+
+    if let Some(hint) = hint {
+        // Honor the server's own schedule in full, even past MAX_DELAY.
+        return Some(hint);
+    }
+
+    let exponential = BASE_DELAY.saturating_mul(2u32.saturating_pow(attempt));
+
+    Some(with_jitter(exponential.min(MAX_DELAY)))
+}
+
+/// A [`Middleware`] that retries rate-limited and transient failures according
+/// to a [`RetryConfig`].
+pub struct RetryMiddleware {
+    config: RetryConfig,
+}
+
+impl RetryMiddleware {
+    pub fn new(config: RetryConfig) -> Self {
+        RetryMiddleware { config }
+    }
+}
+
+#[async_trait::async_trait]
+impl Middleware for RetryMiddleware {
+    async fn handle(
+        &self,
+        request: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        let mut attempt = 0;
+
+        loop {
+            let cloned = request
+                .try_clone()
+                .expect("retryable requests must have a cloneable body");
+            let response = next.clone().run(cloned, extensions).await?;
+
+            if is_retryable(response.status()) {
+                let hint = retry_after(response.headers());
+
+                if let Some(delay) = backoff(&self.config, attempt, hint) {
+                    log::info!(
+                        "Retrying {} after {:?} (attempt {}, status {})",
+                        request.url(),
+                        delay,
+                        attempt + 1,
+                        response.status()
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                    continue;
+                }
+            }
+
+            return Ok(response);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderMap, HeaderValue};
+
+    fn headers(pairs: &[(&str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn retry_after_takes_precedence_over_rate_limit() {
+        let map = headers(&[
+            ("retry-after", "12"),
+            ("x-ratelimit-remaining", "0"),
+            ("x-ratelimit-reset", "0"),
+        ]);
+
+        assert_eq!(retry_after(&map), Some(Duration::from_secs(12)));
+    }
+
+    #[test]
+    fn retry_after_short_circuits_when_requests_remain() {
+        let map = headers(&[("x-ratelimit-remaining", "5"), ("x-ratelimit-reset", "0")]);
+
+        assert_eq!(retry_after(&map), None);
+    }
+
+    #[test]
+    fn retry_after_waits_until_an_exhausted_reset() {
+        let reset = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 30;
+        let map = headers(&[
+            ("x-ratelimit-remaining", "0"),
+            ("x-ratelimit-reset", &reset.to_string()),
+        ]);
+
+        let delay = retry_after(&map).expect("an exhausted limit should wait");
+        assert!(delay > Duration::ZERO && delay <= Duration::from_secs(30));
+    }
+
+    #[test]
+    fn retry_after_clamps_a_reset_in_the_past_to_zero() {
+        let map = headers(&[("x-ratelimit-remaining", "0"), ("x-ratelimit-reset", "1")]);
+
+        assert_eq!(retry_after(&map), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn retry_after_is_absent_without_hints() {
+        assert_eq!(retry_after(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn backoff_stops_when_disabled_or_exhausted() {
+        assert_eq!(backoff(&RetryConfig::disabled(), 0, None), None);
+
+        let config = RetryConfig {
+            max_retries: 3,
+            enabled: true,
+        };
+        assert_eq!(backoff(&config, 3, None), None);
+        assert_eq!(backoff(&config, 4, None), None);
+    }
+
+    #[test]
+    fn backoff_honors_a_hint_in_full() {
+        let config = RetryConfig::default();
+        let hint = MAX_DELAY + Duration::from_secs(60);
+
+        assert_eq!(backoff(&config, 0, Some(hint)), Some(hint));
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_and_caps_at_max_delay() {
+        let config = RetryConfig::default();
+
+        let first = backoff(&config, 0, None).unwrap();
+        assert!(first >= BASE_DELAY / 2 && first <= BASE_DELAY);
+
+        let capped = backoff(&config, 20, None).unwrap();
+        assert!(capped >= MAX_DELAY / 2 && capped <= MAX_DELAY);
+    }
+
+    #[test]
+    fn jitter_keeps_the_delay_in_the_upper_half() {
+        let delay = Duration::from_secs(8);
+
+        for _ in 0..100 {
+            let jittered = with_jitter(delay);
+            assert!(jittered >= delay / 2 && jittered <= delay);
+        }
+    }
+}